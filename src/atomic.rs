@@ -0,0 +1,226 @@
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// A handle to an item stored in a [`StaticPoolAtomic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticPoolAtomicHandle {
+    index: usize,
+}
+
+/// A fixed-sized pool of items that can be allocated and freed from
+/// multiple threads through a shared `&self`, with no external mutex.
+///
+/// Occupancy is tracked with a bitset of `AtomicU32` words: bit `i` of
+/// word `w` marks slot `w * 32 + i` as used. `alloc` claims a slot with
+/// a compare-exchange loop instead of taking `&mut self`, so a
+/// `StaticPoolAtomic` can live in a `static` and hand out slots to
+/// interrupt handlers or worker threads.
+///
+/// `WORDS` must be `ceil(N / 32)`; this isn't checked at compile time
+/// (const generic expressions aren't stable yet), but `new` asserts it.
+///
+/// Items are stored behind `UnsafeCell` and accessed through the
+/// `unsafe` [`StaticPoolAtomic::get`] / [`StaticPoolAtomic::get_mut`].
+/// The caller must uphold the usual aliasing rule: at most one live
+/// `&mut T` to an occupied slot at a time, and no `&T`/`&mut T` to a
+/// slot that has since been freed.
+///
+/// ```
+/// use static_pool::StaticPoolAtomic;
+///
+/// let pool: StaticPoolAtomic<u64, 64, 2> = StaticPoolAtomic::new();
+/// let handle = pool.alloc().unwrap();
+/// unsafe {
+///     *pool.get_mut(handle).unwrap() = 42;
+///     assert_eq!(pool.get(handle), Some(&42));
+/// }
+/// pool.free(handle);
+/// ```
+pub struct StaticPoolAtomic<T, const N: usize, const WORDS: usize> {
+    items: [UnsafeCell<T>; N],
+    words: [AtomicU32; WORDS],
+}
+
+unsafe impl<T: Send, const N: usize, const WORDS: usize> Sync for StaticPoolAtomic<T, N, WORDS> {}
+
+impl<T, const N: usize, const WORDS: usize> Default for StaticPoolAtomic<T, N, WORDS>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize, const WORDS: usize> StaticPoolAtomic<T, N, WORDS>
+where
+    T: Default,
+{
+    pub fn new() -> Self {
+        assert_eq!(WORDS, N.div_ceil(32), "WORDS must equal ceil(N / 32)");
+
+        Self {
+            items: std::array::from_fn(|_| UnsafeCell::new(Default::default())),
+            words: std::array::from_fn(|_| AtomicU32::new(0)),
+        }
+    }
+
+    /// Claims a free slot and returns a handle to it, or `None` if
+    /// every slot is occupied.
+    pub fn alloc(&self) -> Option<StaticPoolAtomicHandle> {
+        for w in 0..WORDS {
+            loop {
+                let word = self.words[w].load(Ordering::Acquire);
+                if word == u32::MAX {
+                    break;
+                }
+
+                let bit = word.trailing_ones() as usize;
+                let index = w * 32 + bit;
+                if index >= N {
+                    break;
+                }
+
+                let claimed = word | (1 << bit);
+                if self.words[w]
+                    .compare_exchange(word, claimed, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+                {
+                    // SAFETY: the CAS above gave this thread exclusive
+                    // ownership of `index` until the matching `free`.
+                    unsafe {
+                        *self.items[index].get() = Default::default();
+                    }
+                    return Some(StaticPoolAtomicHandle { index });
+                }
+                // Lost the race for this word; reload and retry.
+            }
+        }
+
+        None
+    }
+
+    /// Releases the slot referred to by `handle` back to the pool.
+    pub fn free(&self, handle: StaticPoolAtomicHandle) {
+        if handle.index < N {
+            let (w, bit) = Self::word_and_bit(handle.index);
+            self.words[w].fetch_and(!(1u32 << bit), Ordering::AcqRel);
+        }
+    }
+
+    fn is_occupied(&self, handle: StaticPoolAtomicHandle) -> bool {
+        if handle.index >= N {
+            return false;
+        }
+        let (w, bit) = Self::word_and_bit(handle.index);
+        (self.words[w].load(Ordering::Acquire) >> bit) & 1 == 1
+    }
+
+    fn word_and_bit(index: usize) -> (usize, u32) {
+        (index / 32, (index % 32) as u32)
+    }
+
+    /// Returns a reference to the item in `handle`'s slot.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure no other live `&mut T` exists for this
+    /// slot for the duration of the returned reference.
+    pub unsafe fn get(&self, handle: StaticPoolAtomicHandle) -> Option<&T> {
+        if self.is_occupied(handle) {
+            Some(unsafe { &*self.items[handle.index].get() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the item in `handle`'s slot.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure no other live `&T`/`&mut T` exists for
+    /// this slot for the duration of the returned reference.
+    #[allow(clippy::mut_from_ref)]
+    // This mirrors `UnsafeCell::get`'s own contract: `&self` here doesn't
+    // grant shared access to the slot, the `alloc`/`free` bookkeeping
+    // does, so handing out an unaliased `&mut T` from `&self` is exactly
+    // the point, not a bug.
+    pub unsafe fn get_mut(&self, handle: StaticPoolAtomicHandle) -> Option<&mut T> {
+        if self.is_occupied(handle) {
+            Some(unsafe { &mut *self.items[handle.index].get() })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alloc_and_free() {
+        let pool: StaticPoolAtomic<u64, 4, 1> = StaticPoolAtomic::new();
+        let h0 = pool.alloc().unwrap();
+        let h1 = pool.alloc().unwrap();
+        let h2 = pool.alloc().unwrap();
+        let h3 = pool.alloc().unwrap();
+        assert!(pool.alloc().is_none());
+
+        pool.free(h1);
+        let h4 = pool.alloc().unwrap();
+        assert_eq!(h4.index, h1.index);
+
+        unsafe {
+            *pool.get_mut(h0).unwrap() = 7;
+            assert_eq!(pool.get(h0), Some(&7));
+        }
+
+        pool.free(h0);
+        pool.free(h2);
+        pool.free(h3);
+        pool.free(h4);
+    }
+
+    #[test]
+    fn get_after_free_returns_none() {
+        let pool: StaticPoolAtomic<u64, 4, 1> = StaticPoolAtomic::new();
+        let h = pool.alloc().unwrap();
+        pool.free(h);
+        unsafe {
+            assert_eq!(pool.get(h), None);
+        }
+    }
+
+    #[test]
+    fn concurrent_alloc_claims_disjoint_slots() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let pool: Arc<StaticPoolAtomic<u64, 256, 8>> = Arc::new(StaticPoolAtomic::new());
+        let mut threads = Vec::new();
+        for _ in 0..8 {
+            let pool = Arc::clone(&pool);
+            threads.push(thread::spawn(move || {
+                let mut handles = Vec::new();
+                for _ in 0..32 {
+                    handles.push(pool.alloc().unwrap());
+                }
+                handles
+            }));
+        }
+
+        let mut all = Vec::new();
+        for t in threads {
+            all.extend(t.join().unwrap());
+        }
+
+        assert_eq!(all.len(), 256);
+        assert!(pool.alloc().is_none());
+
+        let mut indices: Vec<usize> = all.iter().map(|h| h.index).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(indices.len(), 256);
+    }
+}
@@ -0,0 +1,320 @@
+/// Describes one tier of a [`StaticBytePool`]: how many blocks it has
+/// and how large each block is, in bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct SubpoolSpec {
+    pub num_blocks: usize,
+    pub block_size: usize,
+}
+
+/// The address of a blob stored in a [`StaticBytePool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreAddr {
+    pub subpool_idx: usize,
+    pub block_idx: usize,
+}
+
+/// Errors returned by [`StaticBytePool`]'s operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolError {
+    /// No subpool's block is large enough to hold the data.
+    DataTooLarge,
+    /// The subpool that fits the data has no free blocks left.
+    StoreFull(usize),
+    /// `StoreAddr` doesn't refer to a currently occupied block.
+    InvalidStoreAddr,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Subpool {
+    block_size: usize,
+    num_blocks: usize,
+    block_offset: usize,
+    byte_offset: usize,
+}
+
+impl Subpool {
+    const EMPTY: Subpool = Subpool {
+        block_size: 0,
+        num_blocks: 0,
+        block_offset: 0,
+        byte_offset: 0,
+    };
+}
+
+/// A no-alloc store for variable-length byte blobs, bucketed into
+/// fixed-size subpools (e.g. 4 blocks of 4 bytes, 2 of 8, 1 of 16).
+///
+/// `add` picks the smallest subpool whose `block_size` fits the data
+/// and reserves a free block there, so callers get no-heap-allocation
+/// storage for things like serialized packets without having to pick
+/// one block size for every payload up front.
+///
+/// `NUM_SUBPOOLS`, `TOTAL_BLOCKS` and `TOTAL_BYTES` must agree with the
+/// `specs` passed to [`StaticBytePool::new`] (`TOTAL_BLOCKS` is the sum
+/// of every `num_blocks`, `TOTAL_BYTES` the sum of `num_blocks *
+/// block_size`); this isn't checked at compile time since const
+/// generic expressions aren't stable yet, but `new` asserts it. Specs
+/// must be given in non-decreasing `block_size` order.
+///
+/// ```
+/// use static_pool::{StaticBytePool, SubpoolSpec};
+///
+/// let mut pool: StaticBytePool<3, 7, 48> = StaticBytePool::new([
+///     SubpoolSpec { num_blocks: 4, block_size: 4 },
+///     SubpoolSpec { num_blocks: 2, block_size: 8 },
+///     SubpoolSpec { num_blocks: 1, block_size: 16 },
+/// ]);
+///
+/// let addr = pool.add(b"hi").unwrap();
+/// let mut buf = [0u8; 4];
+/// let n = pool.read(addr, &mut buf).unwrap();
+/// assert_eq!(&buf[..n], b"hi");
+///
+/// pool.delete(addr).unwrap();
+/// assert!(!pool.has_element_at(addr));
+/// ```
+pub struct StaticBytePool<const NUM_SUBPOOLS: usize, const TOTAL_BLOCKS: usize, const TOTAL_BYTES: usize> {
+    subpools: [Subpool; NUM_SUBPOOLS],
+    storage: [u8; TOTAL_BYTES],
+    used: [bool; TOTAL_BLOCKS],
+    lengths: [usize; TOTAL_BLOCKS],
+}
+
+impl<const NUM_SUBPOOLS: usize, const TOTAL_BLOCKS: usize, const TOTAL_BYTES: usize>
+    StaticBytePool<NUM_SUBPOOLS, TOTAL_BLOCKS, TOTAL_BYTES>
+{
+    pub fn new(specs: [SubpoolSpec; NUM_SUBPOOLS]) -> Self {
+        let mut subpools = [Subpool::EMPTY; NUM_SUBPOOLS];
+        let mut block_offset = 0;
+        let mut byte_offset = 0;
+
+        for i in 0..NUM_SUBPOOLS {
+            let spec = specs[i];
+            debug_assert!(
+                i == 0 || spec.block_size >= specs[i - 1].block_size,
+                "subpool specs must be given in non-decreasing block_size order"
+            );
+
+            subpools[i] = Subpool {
+                block_size: spec.block_size,
+                num_blocks: spec.num_blocks,
+                block_offset,
+                byte_offset,
+            };
+            block_offset += spec.num_blocks;
+            byte_offset += spec.num_blocks * spec.block_size;
+        }
+
+        assert_eq!(
+            block_offset, TOTAL_BLOCKS,
+            "TOTAL_BLOCKS must equal the sum of every spec's num_blocks"
+        );
+        assert_eq!(
+            byte_offset, TOTAL_BYTES,
+            "TOTAL_BYTES must equal the sum of num_blocks * block_size across specs"
+        );
+
+        Self {
+            subpools,
+            storage: [0; TOTAL_BYTES],
+            used: [false; TOTAL_BLOCKS],
+            lengths: [0; TOTAL_BLOCKS],
+        }
+    }
+
+    fn global_block(&self, addr: StoreAddr) -> Result<usize, PoolError> {
+        let sp = self
+            .subpools
+            .get(addr.subpool_idx)
+            .ok_or(PoolError::InvalidStoreAddr)?;
+
+        if addr.block_idx >= sp.num_blocks {
+            return Err(PoolError::InvalidStoreAddr);
+        }
+
+        Ok(sp.block_offset + addr.block_idx)
+    }
+
+    /// Copies `data` into the smallest block that fits it and returns
+    /// its address.
+    pub fn add(&mut self, data: &[u8]) -> Result<StoreAddr, PoolError> {
+        let subpool_idx = self
+            .subpools
+            .iter()
+            .position(|sp| sp.block_size >= data.len())
+            .ok_or(PoolError::DataTooLarge)?;
+
+        let sp = self.subpools[subpool_idx];
+        let block_idx = (0..sp.num_blocks)
+            .find(|&b| !self.used[sp.block_offset + b])
+            .ok_or(PoolError::StoreFull(subpool_idx))?;
+
+        let global = sp.block_offset + block_idx;
+        let start = sp.byte_offset + block_idx * sp.block_size;
+
+        self.storage[start..start + data.len()].copy_from_slice(data);
+        self.lengths[global] = data.len();
+        self.used[global] = true;
+
+        Ok(StoreAddr {
+            subpool_idx,
+            block_idx,
+        })
+    }
+
+    /// Copies up to `buf.len()` stored bytes into `buf` and returns
+    /// the number of bytes copied.
+    pub fn read(&self, addr: StoreAddr, buf: &mut [u8]) -> Result<usize, PoolError> {
+        let global = self.global_block(addr)?;
+        if !self.used[global] {
+            return Err(PoolError::InvalidStoreAddr);
+        }
+
+        let sp = self.subpools[addr.subpool_idx];
+        let len = self.lengths[global];
+        let start = sp.byte_offset + addr.block_idx * sp.block_size;
+
+        let n = len.min(buf.len());
+        buf[..n].copy_from_slice(&self.storage[start..start + n]);
+        Ok(n)
+    }
+
+    /// Runs `f` over the stored bytes at `addr` in place.
+    pub fn modify<F>(&mut self, addr: StoreAddr, f: F) -> Result<(), PoolError>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let global = self.global_block(addr)?;
+        if !self.used[global] {
+            return Err(PoolError::InvalidStoreAddr);
+        }
+
+        let sp = self.subpools[addr.subpool_idx];
+        let len = self.lengths[global];
+        let start = sp.byte_offset + addr.block_idx * sp.block_size;
+
+        f(&mut self.storage[start..start + len]);
+        Ok(())
+    }
+
+    /// Frees the block at `addr`.
+    pub fn delete(&mut self, addr: StoreAddr) -> Result<(), PoolError> {
+        let global = self.global_block(addr)?;
+        if !self.used[global] {
+            return Err(PoolError::InvalidStoreAddr);
+        }
+
+        self.used[global] = false;
+        self.lengths[global] = 0;
+        Ok(())
+    }
+
+    /// Returns `true` if `addr` refers to a currently occupied block.
+    pub fn has_element_at(&self, addr: StoreAddr) -> bool {
+        matches!(self.global_block(addr), Ok(global) if self.used[global])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_pool() -> StaticBytePool<3, 7, 48> {
+        StaticBytePool::new([
+            SubpoolSpec {
+                num_blocks: 4,
+                block_size: 4,
+            },
+            SubpoolSpec {
+                num_blocks: 2,
+                block_size: 8,
+            },
+            SubpoolSpec {
+                num_blocks: 1,
+                block_size: 16,
+            },
+        ])
+    }
+
+    #[test]
+    fn add_picks_smallest_fitting_subpool() {
+        let mut pool = new_pool();
+
+        let small = pool.add(b"hi").unwrap();
+        assert_eq!(small.subpool_idx, 0);
+
+        let medium = pool.add(b"hello!!").unwrap();
+        assert_eq!(medium.subpool_idx, 1);
+
+        let large = pool.add(b"0123456789abcdef").unwrap();
+        assert_eq!(large.subpool_idx, 2);
+    }
+
+    #[test]
+    fn read_returns_the_stored_bytes() {
+        let mut pool = new_pool();
+        let addr = pool.add(b"abc").unwrap();
+
+        let mut buf = [0u8; 4];
+        let n = pool.read(addr, &mut buf).unwrap();
+        assert_eq!(&buf[..n], b"abc");
+    }
+
+    #[test]
+    fn modify_mutates_in_place() {
+        let mut pool = new_pool();
+        let addr = pool.add(b"abc").unwrap();
+
+        pool.modify(addr, |bytes| bytes.make_ascii_uppercase()).unwrap();
+
+        let mut buf = [0u8; 4];
+        let n = pool.read(addr, &mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ABC");
+    }
+
+    #[test]
+    fn delete_frees_the_block_and_invalidates_the_addr() {
+        let mut pool = new_pool();
+        let addr = pool.add(b"abc").unwrap();
+
+        assert!(pool.has_element_at(addr));
+        pool.delete(addr).unwrap();
+        assert!(!pool.has_element_at(addr));
+        assert_eq!(pool.read(addr, &mut [0u8; 4]), Err(PoolError::InvalidStoreAddr));
+    }
+
+    #[test]
+    fn data_too_large_is_rejected() {
+        let mut pool = new_pool();
+        let err = pool.add(&[0u8; 17]).unwrap_err();
+        assert_eq!(err, PoolError::DataTooLarge);
+    }
+
+    #[test]
+    fn store_full_is_reported_per_subpool() {
+        let mut pool = new_pool();
+        for _ in 0..4 {
+            pool.add(b"x").unwrap();
+        }
+        let err = pool.add(b"y").unwrap_err();
+        assert_eq!(err, PoolError::StoreFull(0));
+    }
+
+    #[test]
+    fn freed_blocks_can_be_reused() {
+        let mut pool = new_pool();
+        for _ in 0..4 {
+            pool.add(b"x").unwrap();
+        }
+        assert_eq!(pool.add(b"y").unwrap_err(), PoolError::StoreFull(0));
+
+        pool.delete(StoreAddr {
+            subpool_idx: 0,
+            block_idx: 2,
+        })
+        .unwrap();
+
+        let addr = pool.add(b"y").unwrap();
+        assert_eq!(addr.subpool_idx, 0);
+    }
+}
@@ -1,5 +1,14 @@
 #![allow(dead_code)]
 
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+
+mod atomic;
+mod byte_pool;
+
+pub use atomic::{StaticPoolAtomic, StaticPoolAtomicHandle};
+pub use byte_pool::{PoolError, StaticBytePool, StoreAddr, SubpoolSpec};
+
 /// A fixed-sized static pool of items.
 ///
 /// `StaticPool` manages `N` items of type `T`. The items
@@ -10,73 +19,324 @@
 /// use static_pool::StaticPool;
 ///
 /// let mut pool: StaticPool<u64, 128> = StaticPool::new();
-/// let handle = pool.alloc().unwrap();
+/// let handle = pool.alloc(0).unwrap();
 /// let num = pool.get_mut(handle).unwrap();
 /// *num = 128;
 /// assert_eq!(pool.get(handle), Some(&128));
 /// ```
 ///
 pub struct StaticPool<T, const N: usize> {
-    items: [T; N],
-    free: [bool; N],
+    items: [MaybeUninit<T>; N],
+    occupied: [bool; N],
+    generations: [u32; N],
+    /// Singly-linked free-list: `next[i]` is the slot that follows `i`
+    /// in the chain of vacant slots. The links are kept in this array
+    /// beside the item storage, not inside the slots, and let `alloc`
+    /// find the next free index without scanning.
+    next: [Option<usize>; N],
+    free_head: Option<usize>,
     len: usize,
 }
 
-pub type StaticPoolHandle = usize;
+/// A handle to an item stored in a `StaticPool`.
+///
+/// Besides the slot `index`, a handle carries the `generation`
+/// the slot was in when the handle was issued. `StaticPool` bumps
+/// a slot's generation every time it changes occupancy (on both
+/// `alloc` and `free`), so a handle to a slot that has since been
+/// freed and reallocated no longer matches and is rejected by
+/// `get`, `get_mut` and `free` instead of silently aliasing the
+/// new occupant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticPoolHandle {
+    index: usize,
+    generation: u32,
+}
 
-impl<T, const N: usize> StaticPool<T, N>
-where
-    T: Default,
-{
+impl<T, const N: usize> Default for StaticPool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> StaticPool<T, N> {
     pub fn new() -> Self {
+        let mut next = [None; N];
+        for (i, slot) in next.iter_mut().enumerate() {
+            *slot = if i + 1 < N { Some(i + 1) } else { None };
+        }
+
         Self {
-            items: std::array::from_fn(|_| Default::default()),
-            free: [true; N],
+            items: std::array::from_fn(|_| MaybeUninit::uninit()),
+            occupied: [false; N],
+            generations: [0; N],
+            next,
+            free_head: if N > 0 { Some(0) } else { None },
             len: 0,
         }
     }
 
-    fn next_free_handle(&mut self) -> Option<StaticPoolHandle> {
-        for i in 0..N {
-            if self.free[i] {
-                self.free[i] = false;
-                let handle = i + 1;
-                return Some(handle);
-            }
-        }
+    fn pop_free(&mut self) -> Option<usize> {
+        let index = self.free_head?;
+        self.free_head = self.next[index];
+        Some(index)
+    }
 
-        None
+    fn push_free(&mut self, index: usize) {
+        self.next[index] = self.free_head;
+        self.free_head = Some(index);
     }
 
-    pub fn alloc(&mut self) -> Option<StaticPoolHandle> {
-        let handle = self.next_free_handle()?;
-        self.items[handle - 1] = Default::default();
-        Some(handle)
+    /// Allocates a slot and writes `value` into it.
+    pub fn alloc(&mut self, value: T) -> Option<StaticPoolHandle> {
+        let index = self.pop_free()?;
+        self.items[index].write(value);
+        self.occupied[index] = true;
+        self.generations[index] += 1;
+        self.len += 1;
+        Some(StaticPoolHandle {
+            index,
+            generation: self.generations[index],
+        })
+    }
+
+    /// Allocates a slot and returns an RAII [`Pooled`] guard over it.
+    ///
+    /// The guard derefs straight to `T` and frees the slot automatically
+    /// when dropped, so callers don't have to pair `alloc`/`get`/`free`
+    /// calls by hand. Use the raw handle API instead when a handle needs
+    /// to be stored in a struct or outlive the borrow on the pool.
+    pub fn alloc_guard(&mut self, value: T) -> Option<Pooled<'_, T, N>> {
+        let handle = self.alloc(value)?;
+        Some(Pooled { pool: self, handle })
     }
 
     pub fn free(&mut self, handle: StaticPoolHandle) {
-        if handle > 0 && handle <= N {
-            if !self.free[handle - 1] {
-                self.free[handle - 1] = true;
+        if self.is_valid(handle) {
+            // SAFETY: `is_valid` confirms this slot is occupied, so its
+            // `MaybeUninit` was written to by a matching `alloc` and
+            // hasn't been read out of or dropped since.
+            unsafe {
+                self.items[handle.index].assume_init_drop();
             }
+            self.occupied[handle.index] = false;
+            self.generations[handle.index] += 1;
+            self.push_free(handle.index);
+            self.len -= 1;
         }
     }
 
+    /// Returns `true` if `handle` still refers to a live, occupied slot.
+    pub fn is_valid(&self, handle: StaticPoolHandle) -> bool {
+        handle.index < N && self.occupied[handle.index] && self.generations[handle.index] == handle.generation
+    }
+
     pub fn get(&self, handle: StaticPoolHandle) -> Option<&T> {
-        if handle > 0 && handle <= N && !self.free[handle - 1] {
-            Some(&self.items[handle - 1])
+        if self.is_valid(handle) {
+            // SAFETY: see `free`.
+            Some(unsafe { self.items[handle.index].assume_init_ref() })
         } else {
             None
         }
     }
 
     pub fn get_mut(&mut self, handle: StaticPoolHandle) -> Option<&mut T> {
-        if handle > 0 && handle <= N && !self.free[handle - 1] {
-            Some(&mut self.items[handle - 1])
+        if self.is_valid(handle) {
+            // SAFETY: see `free`.
+            Some(unsafe { self.items[handle.index].assume_init_mut() })
         } else {
             None
         }
     }
+
+    /// Returns the number of currently occupied slots.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no slots are currently occupied.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the total number of slots, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Iterates over the occupied slots, yielding each one's handle
+    /// alongside a reference to its item.
+    pub fn iter(&self) -> impl Iterator<Item = (StaticPoolHandle, &T)> {
+        self.items.iter().enumerate().filter_map(move |(i, item)| {
+            if self.occupied[i] {
+                // SAFETY: occupied[i] means this slot was written by alloc.
+                Some((
+                    StaticPoolHandle {
+                        index: i,
+                        generation: self.generations[i],
+                    },
+                    unsafe { item.assume_init_ref() },
+                ))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Iterates over the occupied slots, yielding each one's handle
+    /// alongside a mutable reference to its item.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (StaticPoolHandle, &mut T)> {
+        let occupied = self.occupied;
+        let generations = self.generations;
+        self.items
+            .iter_mut()
+            .enumerate()
+            .filter_map(move |(i, item)| {
+                if occupied[i] {
+                    // SAFETY: occupied[i] means this slot was written by alloc.
+                    Some((
+                        StaticPoolHandle {
+                            index: i,
+                            generation: generations[i],
+                        },
+                        unsafe { item.assume_init_mut() },
+                    ))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Frees every occupied slot, invalidating all outstanding handles.
+    pub fn clear(&mut self) {
+        for i in 0..N {
+            if self.occupied[i] {
+                // SAFETY: occupied[i] means this slot was written by alloc.
+                unsafe {
+                    self.items[i].assume_init_drop();
+                }
+                self.occupied[i] = false;
+                self.generations[i] += 1;
+            }
+            self.next[i] = if i + 1 < N { Some(i + 1) } else { None };
+        }
+        self.free_head = if N > 0 { Some(0) } else { None };
+        self.len = 0;
+    }
+
+    /// Returns an iterator that frees each occupied slot as it reaches
+    /// it and yields the owned item that was stored there. Slots the
+    /// iterator hasn't reached when it's dropped stay occupied.
+    pub fn drain(&mut self) -> Drain<'_, T, N> {
+        Drain {
+            pool: self,
+            index: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> StaticPool<T, N>
+where
+    T: Default,
+{
+    /// Convenience over [`StaticPool::alloc`] for `Default` types.
+    pub fn alloc_default(&mut self) -> Option<StaticPoolHandle> {
+        self.alloc(Default::default())
+    }
+
+    /// Convenience over [`StaticPool::alloc_guard`] for `Default` types.
+    pub fn alloc_guard_default(&mut self) -> Option<Pooled<'_, T, N>> {
+        self.alloc_guard(Default::default())
+    }
+}
+
+impl<T, const N: usize> Drop for StaticPool<T, N> {
+    fn drop(&mut self) {
+        for i in 0..N {
+            if self.occupied[i] {
+                // SAFETY: occupied[i] means this slot holds a live `T`
+                // written by `alloc` that hasn't been dropped yet.
+                unsafe {
+                    self.items[i].assume_init_drop();
+                }
+            }
+        }
+    }
+}
+
+/// Iterator returned by [`StaticPool::drain`].
+pub struct Drain<'a, T, const N: usize> {
+    pool: &'a mut StaticPool<T, N>,
+    index: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for Drain<'a, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.index < N {
+            let i = self.index;
+            self.index += 1;
+            if self.pool.occupied[i] {
+                self.pool.occupied[i] = false;
+                self.pool.generations[i] += 1;
+                self.pool.push_free(i);
+                self.pool.len -= 1;
+                // SAFETY: occupied[i] means this slot was written by
+                // alloc; reading it out here hands ownership to the
+                // caller, so it must not be dropped again.
+                return Some(unsafe { self.pool.items[i].assume_init_read() });
+            }
+        }
+        None
+    }
+}
+
+/// An RAII guard owning a [`StaticPool`] slot, returned by
+/// [`StaticPool::alloc_guard`].
+///
+/// `Pooled` derefs to `T`, so it can be used like a `Box<T>` backed by
+/// pool storage, and frees its slot when dropped. It borrows the pool
+/// for as long as it's alive, so only one guard (or other pool access)
+/// can exist at a time — use the raw handle API instead when several
+/// items need to be live across the pool at once.
+pub struct Pooled<'a, T, const N: usize> {
+    pool: &'a mut StaticPool<T, N>,
+    handle: StaticPoolHandle,
+}
+
+impl<'a, T, const N: usize> Pooled<'a, T, N> {
+    /// Returns the handle of the slot this guard owns. The guard holds
+    /// the pool's mutable borrow, so the handle can only be used with
+    /// the pool after the guard is dropped, by which point the slot has
+    /// been freed and the handle is no longer valid.
+    pub fn handle(&self) -> StaticPoolHandle {
+        self.handle
+    }
+}
+
+impl<'a, T, const N: usize> Deref for Pooled<'a, T, N> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.pool
+            .get(self.handle)
+            .expect("Pooled's slot stays allocated for the guard's whole lifetime")
+    }
+}
+
+impl<'a, T, const N: usize> DerefMut for Pooled<'a, T, N> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.pool
+            .get_mut(self.handle)
+            .expect("Pooled's slot stays allocated for the guard's whole lifetime")
+    }
+}
+
+impl<'a, T, const N: usize> Drop for Pooled<'a, T, N> {
+    fn drop(&mut self) {
+        self.pool.free(self.handle);
+    }
 }
 
 #[cfg(test)]
@@ -87,7 +347,7 @@ mod tests {
     #[test]
     fn it_works() {
         let mut p: StaticPool<i32, 1024> = StaticPool::new();
-        let handle = p.alloc().unwrap();
+        let handle = p.alloc_default().unwrap();
         assert_eq!(p.get(handle), Some(&0));
 
         *p.get_mut(handle).unwrap() = 100;
@@ -106,7 +366,7 @@ mod tests {
     #[test]
     fn test_with_data() {
         let mut p: StaticPool<Data, 1024> = StaticPool::new();
-        let handle = p.alloc().unwrap();
+        let handle = p.alloc_default().unwrap();
         let data = p.get_mut(handle).unwrap();
         data.x = 128;
         data.s = "Some data".to_owned();
@@ -118,23 +378,201 @@ mod tests {
     #[test]
     fn test_alloc_free() {
         let mut p: StaticPool<u64, 4> = StaticPool::new();
-        let handle = p.alloc();
-        assert_eq!(handle, Some(1));
-        let handle = p.alloc();
-        assert_eq!(handle, Some(2));
-        let handle = p.alloc();
-        assert_eq!(handle, Some(3));
-        let handle = p.alloc();
-        assert_eq!(handle, Some(4));
-        let handle = p.alloc();
-        assert_eq!(handle, None);
-        let handle = p.alloc();
-        assert_eq!(handle, None);
-
-        p.free(2);
-        let handle = p.alloc();
-        assert_eq!(handle, Some(2));
-        let handle = p.alloc();
-        assert_eq!(handle, None);
+        let h0 = p.alloc_default();
+        assert!(h0.is_some());
+        let h1 = p.alloc_default();
+        assert!(h1.is_some());
+        let h2 = p.alloc_default();
+        assert!(h2.is_some());
+        let h3 = p.alloc_default();
+        assert!(h3.is_some());
+        let h4 = p.alloc_default();
+        assert_eq!(h4, None);
+        let h5 = p.alloc_default();
+        assert_eq!(h5, None);
+
+        let h1 = h1.unwrap();
+        p.free(h1);
+        let realloc = p.alloc_default();
+        assert!(realloc.is_some());
+        let h6 = p.alloc_default();
+        assert_eq!(h6, None);
+    }
+
+    #[test]
+    fn stale_handle_is_rejected_after_realloc() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        let h = p.alloc_default().unwrap();
+        assert!(p.is_valid(h));
+
+        p.free(h);
+        assert!(!p.is_valid(h));
+        assert_eq!(p.get(h), None);
+
+        // Reallocating the same slot must not resurrect the stale handle.
+        let h2 = p.alloc_default().unwrap();
+        assert_eq!(h.index, h2.index);
+        assert_ne!(h.generation, h2.generation);
+        assert!(!p.is_valid(h));
+        assert!(p.is_valid(h2));
+    }
+
+    #[test]
+    fn freed_slots_are_reused_in_lifo_order() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        let h0 = p.alloc_default().unwrap();
+        let _h1 = p.alloc_default().unwrap();
+        let h2 = p.alloc_default().unwrap();
+
+        p.free(h0);
+        p.free(h2);
+
+        // The free-list pushes onto its head, so the most recently
+        // freed slot (h2's) is the first one handed back out.
+        let r0 = p.alloc_default().unwrap();
+        assert_eq!(r0.index, h2.index);
+        let r1 = p.alloc_default().unwrap();
+        assert_eq!(r1.index, h0.index);
+    }
+
+    #[test]
+    fn double_free_is_a_no_op() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        let h = p.alloc_default().unwrap();
+        p.free(h);
+        p.free(h);
+        assert!(!p.is_valid(h));
+    }
+
+    #[test]
+    fn guard_derefs_and_frees_on_drop() {
+        let mut p: StaticPool<Data, 4> = StaticPool::new();
+
+        let handle = {
+            let mut guard = p.alloc_guard_default().unwrap();
+            guard.x = 42;
+            guard.s = "guarded".to_owned();
+            guard.handle()
+        };
+
+        assert!(p.get(handle).is_none());
+    }
+
+    #[test]
+    fn guard_exhausts_pool_like_raw_alloc() {
+        let mut p: StaticPool<u64, 2> = StaticPool::new();
+        p.alloc_default().unwrap();
+        p.alloc_default().unwrap();
+        assert!(p.alloc_guard_default().is_none());
+    }
+
+    #[test]
+    fn len_is_empty_and_capacity() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        assert_eq!(p.capacity(), 4);
+        assert!(p.is_empty());
+
+        let h = p.alloc_default().unwrap();
+        assert_eq!(p.len(), 1);
+        assert!(!p.is_empty());
+
+        p.free(h);
+        assert_eq!(p.len(), 0);
+        assert!(p.is_empty());
+    }
+
+    #[test]
+    fn iter_yields_only_occupied_slots() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        let _h0 = p.alloc(10).unwrap();
+        let h1 = p.alloc(20).unwrap();
+        let _h2 = p.alloc(30).unwrap();
+        p.free(h1);
+
+        let mut values: Vec<u64> = p.iter().map(|(_, v)| *v).collect();
+        values.sort_unstable();
+        assert_eq!(values, vec![10, 30]);
+    }
+
+    #[test]
+    fn iter_mut_allows_updating_in_place() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        p.alloc_default().unwrap();
+        p.alloc_default().unwrap();
+
+        for (_, v) in p.iter_mut() {
+            *v += 1;
+        }
+
+        let total: u64 = p.iter().map(|(_, v)| *v).sum();
+        assert_eq!(total, 2);
+    }
+
+    #[test]
+    fn clear_frees_all_slots_and_invalidates_handles() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        let h0 = p.alloc_default().unwrap();
+        let _h1 = p.alloc_default().unwrap();
+
+        p.clear();
+
+        assert_eq!(p.len(), 0);
+        assert!(!p.is_valid(h0));
+        assert_eq!(p.alloc_default().and_then(|h| p.get(h)).copied(), Some(0));
+    }
+
+    #[test]
+    fn drain_yields_owned_items_and_empties_the_pool() {
+        let mut p: StaticPool<u64, 4> = StaticPool::new();
+        p.alloc(1).unwrap();
+        p.alloc(2).unwrap();
+
+        let mut drained: Vec<u64> = p.drain().collect();
+        drained.sort_unstable();
+        assert_eq!(drained, vec![1, 2]);
+
+        assert!(p.is_empty());
+        assert_eq!(p.capacity(), 4);
+        assert!(p.alloc_default().is_some());
+    }
+
+    struct Droppy(std::rc::Rc<std::cell::Cell<usize>>);
+
+    impl Drop for Droppy {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn free_drops_non_default_values() {
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut p: StaticPool<Droppy, 2> = StaticPool::new();
+        let h = p.alloc(Droppy(drops.clone())).unwrap();
+        assert_eq!(drops.get(), 0);
+
+        p.free(h);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn dropping_the_pool_drops_remaining_occupied_items() {
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        {
+            let mut p: StaticPool<Droppy, 2> = StaticPool::new();
+            p.alloc(Droppy(drops.clone())).unwrap();
+            p.alloc(Droppy(drops.clone())).unwrap();
+        }
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn works_for_types_without_default() {
+        let mut p: StaticPool<String, 2> = StaticPool::new();
+        let h = p.alloc("hello".to_owned()).unwrap();
+        assert_eq!(p.get(h).unwrap(), "hello");
+
+        p.free(h);
+        assert_eq!(p.get(h), None);
     }
 }